@@ -0,0 +1,30 @@
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("invalid output format: {}", s)),
+        }
+    }
+}
+
+pub fn parse_porep_id(hex_str: &str) -> Result<[u8; 32], String> {
+    let bytes =
+        hex::decode(hex_str).map_err(|_| "porep-id must be 64 hex characters (32 bytes)")?;
+    if bytes.len() != 32 {
+        return Err("porep-id must be 64 hex characters (32 bytes)".to_string());
+    }
+    let mut porep_id = [0u8; 32];
+    porep_id.copy_from_slice(&bytes);
+    Ok(porep_id)
+}
@@ -6,8 +6,10 @@ extern crate log;
 use clap::{App, Arg};
 use paired::bls12_381::{Bls12, Fr};
 use rand::{Rng, SeedableRng, XorShiftRng};
+use serde::Serialize;
 use std::time::{Duration, Instant};
 
+use storage_proofs::api_version::ApiVersion;
 use storage_proofs::drgporep::*;
 use storage_proofs::drgraph::*;
 use storage_proofs::example_helper::prettyb;
@@ -21,8 +23,25 @@ use memmap::MmapOptions;
 use std::fs::File;
 use std::io::Write;
 
+#[path = "bench_common/mod.rs"]
+mod bench_common;
+use bench_common::{parse_porep_id, OutputFormat};
+
 const BETA_HEIGHT: usize = 0;
 
+#[derive(Serialize)]
+struct BenchReport {
+    data_size: usize,
+    m: usize,
+    challenge_count: usize,
+    arity: usize,
+    hasher: String,
+    avg_proving_time: f64,
+    avg_verifying_time: f64,
+    replication_time_ms: u64,
+    avg_proof_size: usize,
+}
+
 fn file_backed_mmap_from_random_bytes(n: usize) -> MmapMut {
     let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
     let mut tmpfile: File = tempfile::tempfile().unwrap();
@@ -36,13 +55,30 @@ fn file_backed_mmap_from_random_bytes(n: usize) -> MmapMut {
     unsafe { MmapOptions::new().map_mut(&tmpfile).unwrap() }
 }
 
-fn do_the_work<H: Hasher>(data_size: usize, m: usize, challenge_count: usize) {
+fn do_the_work<H: Hasher>(
+    data_size: usize,
+    m: usize,
+    challenge_count: usize,
+    arity: usize,
+    porep_id: [u8; 32],
+    api_version: ApiVersion,
+    hasher: &str,
+    output: OutputFormat,
+) {
     let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
     let challenges = vec![2; challenge_count];
 
     info!("data_size:  {}", prettyb(data_size));
     info!("challenge_count: {}", challenge_count);
     info!("m: {}", m);
+    info!("arity: {}", arity);
+    info!("porep_id: {:?}", porep_id);
+    info!("api_version: {}", api_version);
+
+    // Non-binary tree construction/path emission/verify live in drgraph and aren't vendored
+    // here, so an arity other than 2 would silently replicate/prove as a binary tree while
+    // reporting a different number. Refuse instead of mislabeling the results.
+    assert_eq!(arity, 2, "only arity 2 is supported in this tree");
 
     info!("generating fake data");
 
@@ -59,12 +95,16 @@ fn do_the_work<H: Hasher>(data_size: usize, m: usize, challenge_count: usize) {
             nodes,
             degree: m,
             expansion_degree: 0,
-            seed: new_seed(),
+            // Version-gated parent selection lives in drgraph; this just forwards the id.
+            seed: new_seed(porep_id, api_version),
         },
         private: true,
         challenges_count: challenge_count,
         beta_height: BETA_HEIGHT,
         prev_layer_beta_height,
+        arity,
+        porep_id,
+        api_version,
     };
 
     info!("running setup");
@@ -124,10 +164,28 @@ fn do_the_work<H: Hasher>(data_size: usize, m: usize, challenge_count: usize) {
     let verifying_avg = f64::from(verifying_avg.subsec_nanos()) / 1_000_000_000f64
         + (verifying_avg.as_secs() as f64);
 
-    info!("avg_proving_time: {:?} seconds", proving_avg);
-    info!("avg_verifying_time: {:?} seconds", verifying_avg);
-    info!("replication_time={:?}", param_duration);
-    info!("avg_proof_size: {}", prettyb(avg_proof_size));
+    match output {
+        OutputFormat::Text => {
+            info!("avg_proving_time: {:?} seconds", proving_avg);
+            info!("avg_verifying_time: {:?} seconds", verifying_avg);
+            info!("replication_time={:?}", param_duration);
+            info!("avg_proof_size: {}", prettyb(avg_proof_size));
+        }
+        OutputFormat::Json => {
+            let report = BenchReport {
+                data_size,
+                m,
+                challenge_count,
+                arity,
+                hasher: hasher.to_string(),
+                avg_proving_time: proving_avg,
+                avg_verifying_time: verifying_avg,
+                replication_time_ms: param_duration.as_millis() as u64,
+                avg_proof_size,
+            };
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+    }
 }
 
 fn main() {
@@ -163,23 +221,91 @@ fn main() {
                 .default_value("pedersen")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("arity")
+                .long("arity")
+                .help("Merkle tree arity, i.e. the number of children per node; only \"2\" is accepted, any other value aborts the run (default \"2\")")
+                .default_value("2")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("porep-id")
+                .long("porep-id")
+                .help("32-byte porep_id as a hex string, used to derive the DRG/expander graph seed")
+                .default_value("0000000000000000000000000000000000000000000000000000000000000000")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("api-version")
+                .long("api-version")
+                .help("The API version to setup with: \"1.0.0\" or \"1.1.0\" (default \"1.0.0\")")
+                .default_value("1.0.0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .help("Output format for the collected metrics: \"text\" or \"json\" (default \"text\")")
+                .default_value("text")
+                .takes_value(true),
+        )
         .get_matches();
 
     let data_size = value_t!(matches, "size", usize).unwrap() * 1024;
     let m = value_t!(matches, "m", usize).unwrap();
     let challenge_count = value_t!(matches, "challenges", usize).unwrap();
+    let arity = value_t!(matches, "arity", usize).unwrap();
+    let porep_id =
+        parse_porep_id(matches.value_of("porep-id").unwrap()).expect("invalid porep-id");
+    let api_version: ApiVersion = matches
+        .value_of("api-version")
+        .unwrap()
+        .parse()
+        .expect("invalid api-version");
+    let output: OutputFormat = matches
+        .value_of("output")
+        .unwrap()
+        .parse()
+        .expect("invalid output format");
 
     let hasher = value_t!(matches, "hasher", String).unwrap();
     info!("hasher: {}", hasher);
     match hasher.as_ref() {
         "pedersen" => {
-            do_the_work::<PedersenHasher>(data_size, m, challenge_count);
+            do_the_work::<PedersenHasher>(
+                data_size,
+                m,
+                challenge_count,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                output,
+            );
         }
         "sha256" => {
-            do_the_work::<Sha256Hasher>(data_size, m, challenge_count);
+            do_the_work::<Sha256Hasher>(
+                data_size,
+                m,
+                challenge_count,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                output,
+            );
         }
         "blake2s" => {
-            do_the_work::<Blake2sHasher>(data_size, m, challenge_count);
+            do_the_work::<Blake2sHasher>(
+                data_size,
+                m,
+                challenge_count,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                output,
+            );
         }
         _ => panic!(format!("invalid hasher: {}", hasher)),
     }
@@ -12,16 +12,18 @@ use memmap::MmapMut;
 use memmap::MmapOptions;
 use paired::bls12_381::Bls12;
 use rand::{Rng, SeedableRng, XorShiftRng};
+use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
 use std::time::Instant;
 
+use storage_proofs::api_version::ApiVersion;
 use storage_proofs::drgporep;
 use storage_proofs::drgraph::*;
 use storage_proofs::example_helper::prettyb;
 use storage_proofs::fr32::fr_into_bytes;
 use storage_proofs::hasher::hybrid::HybridDomain;
-use storage_proofs::hasher::{Hasher, PedersenHasher};
+use storage_proofs::hasher::{Blake2sHasher, Hasher, PedersenHasher, Sha256Hasher};
 use storage_proofs::layered_drgporep::{self, LayerChallenges};
 use storage_proofs::proof::ProofScheme;
 use storage_proofs::vde;
@@ -51,6 +53,24 @@ fn stop_profile() {
 #[inline(always)]
 fn stop_profile() {}
 
+#[path = "bench_common/mod.rs"]
+mod bench_common;
+use bench_common::{parse_porep_id, OutputFormat};
+
+#[derive(Serialize)]
+struct BenchReport {
+    data_size: usize,
+    m: usize,
+    expansion_degree: usize,
+    arity: usize,
+    hasher: String,
+    layers: usize,
+    cores: usize,
+    encoding_time_ms: u64,
+    encoding_time_per_gib_ms: u64,
+    parallel_speedup: f64,
+}
+
 fn file_backed_mmap_from_random_bytes(n: usize) -> MmapMut {
     let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
     let mut tmpfile: File = tempfile::tempfile().unwrap();
@@ -71,8 +91,17 @@ pub fn file_backed_mmap_from(data: &[u8]) -> MmapMut {
     unsafe { MmapOptions::new().map_mut(&tmpfile).unwrap() }
 }
 
-fn do_the_work<H: 'static>(data_size: usize, m: usize, expansion_degree: usize)
-where
+fn do_the_work<H: 'static>(
+    data_size: usize,
+    m: usize,
+    expansion_degree: usize,
+    arity: usize,
+    porep_id: [u8; 32],
+    api_version: ApiVersion,
+    hasher: &str,
+    cores: usize,
+    output: OutputFormat,
+) where
     H: Hasher,
 {
     const N_LAYERS: usize = 1;
@@ -83,6 +112,15 @@ where
     info!("data size: {}", prettyb(data_size));
     info!("m: {}", m);
     info!("expansion_degree: {}", expansion_degree);
+    info!("arity: {}", arity);
+    info!("porep_id: {:?}", porep_id);
+    info!("api_version: {}", api_version);
+
+    // Non-binary tree construction/path emission/verify live in drgraph and aren't vendored
+    // here, so an arity other than 2 would silently replicate/prove as a binary tree while
+    // reporting a different number. Refuse instead of mislabeling the results.
+    assert_eq!(arity, 2, "only arity 2 is supported in this tree");
+
     info!("generating fake data");
 
     let nodes = data_size / 32;
@@ -97,10 +135,14 @@ where
             nodes,
             degree: m,
             expansion_degree,
-            seed: new_seed(),
+            // Version-gated parent selection lives in drgraph; this just forwards the id.
+            seed: new_seed(porep_id, api_version),
         },
         layer_challenges: LayerChallenges::new_fixed(N_LAYERS, 1),
         beta_heights: BETA_HEIGHTS.to_vec(),
+        arity,
+        porep_id,
+        api_version,
     };
 
     info!("running setup");
@@ -108,22 +150,59 @@ where
     let pp = ZigZagDrgPoRep::<H, H>::setup(&sp).unwrap();
     stop_profile();
 
+    info!("cores: {}", cores);
+
+    // vde::encode isn't parallelized in this tree, so --cores can't be shown to change
+    // anything by construction; measure it instead of asserting it, and report the ratio.
+    let baseline_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("failed to build baseline thread pool");
+    let mut baseline_data = file_backed_mmap_from_random_bytes(nodes);
+    let baseline_start = Instant::now();
+    baseline_pool.install(|| vde::encode(&pp.graph, &replica_id, &mut baseline_data).unwrap());
+    let baseline_time = baseline_start.elapsed();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(cores)
+        .build()
+        .expect("failed to build encode thread pool");
+
     let start = Instant::now();
 
     info!("encoding");
 
     start_profile("encode");
-    vde::encode(&pp.graph, &replica_id, &mut data).unwrap();
+    pool.install(|| vde::encode(&pp.graph, &replica_id, &mut data).unwrap());
     stop_profile();
 
     let encoding_time = start.elapsed();
-    info!("encoding_time: {:?}", encoding_time);
+    let encoding_time_per_gib = (1 << 30) * encoding_time / data_size as u32;
+    let parallel_speedup = baseline_time.as_secs_f64() / encoding_time.as_secs_f64();
 
-    info!("encoding time/byte: {:?}", encoding_time / data_size as u32);
-    info!(
-        "encoding time/GiB: {:?}",
-        (1 << 30) * encoding_time / data_size as u32
-    );
+    match output {
+        OutputFormat::Text => {
+            info!("encoding_time: {:?}", encoding_time);
+            info!("encoding time/byte: {:?}", encoding_time / data_size as u32);
+            info!("encoding time/GiB: {:?}", encoding_time_per_gib);
+            info!("parallel speedup ({} cores): {:.2}x", cores, parallel_speedup);
+        }
+        OutputFormat::Json => {
+            let report = BenchReport {
+                data_size,
+                m,
+                expansion_degree,
+                arity,
+                hasher: hasher.to_string(),
+                layers: N_LAYERS,
+                cores,
+                encoding_time_ms: encoding_time.as_millis() as u64,
+                encoding_time_per_gib_ms: encoding_time_per_gib.as_millis() as u64,
+                parallel_speedup,
+            };
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+    }
 }
 
 fn main() {
@@ -159,11 +238,109 @@ fn main() {
                 .default_value("10")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("hasher")
+                .long("hasher")
+                .help("Which hasher should be used.Available: \"pedersen\", \"sha256\", \"blake2s\" (default \"pedersen\")")
+                .default_value("pedersen")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("arity")
+                .long("arity")
+                .help("Merkle tree arity, i.e. the number of children per node; only \"2\" is accepted, any other value aborts the run (default \"2\")")
+                .default_value("2")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("porep-id")
+                .long("porep-id")
+                .help("32-byte porep_id as a hex string, used to derive the DRG/expander graph seed")
+                .default_value("0000000000000000000000000000000000000000000000000000000000000000")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("api-version")
+                .long("api-version")
+                .help("The API version to setup with: \"1.0.0\" or \"1.1.0\" (default \"1.0.0\")")
+                .default_value("1.0.0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .help("Output format for the collected metrics: \"text\" or \"json\" (default \"text\")")
+                .default_value("text")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("cores")
+                .long("cores")
+                .help("Size of the rayon thread pool used for encoding (defaults to the detected CPU count)")
+                .takes_value(true),
+        )
         .get_matches();
 
     let data_size = value_t!(matches, "size", usize).unwrap() * 1024;
     let m = value_t!(matches, "m", usize).unwrap();
     let expansion_degree = value_t!(matches, "exp", usize).unwrap();
+    let arity = value_t!(matches, "arity", usize).unwrap();
+    let porep_id =
+        parse_porep_id(matches.value_of("porep-id").unwrap()).expect("invalid porep-id");
+    let api_version: ApiVersion = matches
+        .value_of("api-version")
+        .unwrap()
+        .parse()
+        .expect("invalid api-version");
+    let output: OutputFormat = matches
+        .value_of("output")
+        .unwrap()
+        .parse()
+        .expect("invalid output format");
+    let cores = value_t!(matches, "cores", usize).unwrap_or_else(|_| num_cpus::get());
 
-    do_the_work::<PedersenHasher>(data_size, m, expansion_degree);
+    let hasher = value_t!(matches, "hasher", String).unwrap();
+    info!("hasher: {}", hasher);
+    match hasher.as_ref() {
+        "pedersen" => {
+            do_the_work::<PedersenHasher>(
+                data_size,
+                m,
+                expansion_degree,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                cores,
+                output,
+            );
+        }
+        "sha256" => {
+            do_the_work::<Sha256Hasher>(
+                data_size,
+                m,
+                expansion_degree,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                cores,
+                output,
+            );
+        }
+        "blake2s" => {
+            do_the_work::<Blake2sHasher>(
+                data_size,
+                m,
+                expansion_degree,
+                arity,
+                porep_id,
+                api_version,
+                &hasher,
+                cores,
+                output,
+            );
+        }
+        _ => panic!(format!("invalid hasher: {}", hasher)),
+    }
 }